@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 把字节数格式化成带单位的可读字符串，例如 1.42 GB
+pub fn human_readable_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KB", "MB", "GB", "TB", "PB"];
+
+    let mut value = bytes as f64;
+    let mut unit_index = 0;
+    while value >= 1024.0 && unit_index < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[unit_index])
+    } else {
+        format!("{:.2} {}", value, UNITS[unit_index])
+    }
+}
+
+// 递归遍历目录，累加其中所有文件的大小。max_depth 限制递归深度
+// （depth 0 只统计 path 本身这一层的文件，不进入子目录）；比 max_depth
+// 更深的文件不计入总和，max_depth 调小会漏掉更深层的文件，请按需传入足够大
+// 的值（调用方默认传 usize::MAX）。
+pub fn walk_dir_size(path: &Path, max_depth: usize) -> io::Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+
+    if metadata.is_file() {
+        return Ok(metadata.len());
+    }
+
+    if !metadata.is_dir() {
+        // 符号链接等不计入大小
+        return Ok(0);
+    }
+
+    // 单个子目录/文件不可读（权限不足、竞态删除等）不应该让整个统计失败，
+    // 和 `du` 一样跳过读不到的条目继续累加其余部分
+    let read_dir = match fs::read_dir(path) {
+        Ok(read_dir) => read_dir,
+        Err(_) => return Ok(0),
+    };
+
+    let mut total = 0u64;
+    for entry in read_dir {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        let entry_path = entry.path();
+        let entry_metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if entry_metadata.is_dir() {
+            if max_depth > 0 {
+                // 子目录递归失败（竞态删除、权限不足等）不应该让整棵树的统计失败，
+                // 和上面跳过不可读条目的思路一致：这一支算 0，继续累加其它部分
+                total += walk_dir_size(&entry_path, max_depth - 1).unwrap_or(0);
+            }
+        } else if entry_metadata.is_file() {
+            total += entry_metadata.len();
+        }
+    }
+
+    Ok(total)
+}
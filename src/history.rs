@@ -0,0 +1,115 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::state::AppState;
+
+// 历史采样的间隔与每条时间序列保留的最大样本数
+const SAMPLE_INTERVAL_MS: u64 = 2000;
+const HISTORY_CAPACITY: usize = 300;
+
+// 单个指标在某一时刻的取值
+#[derive(Clone, serde::Serialize, Debug)]
+pub struct HistoryPoint {
+    pub timestamp: String,
+    pub value: f64,
+}
+
+// 单个网络接口在某一时刻的收发速率
+#[derive(Clone, serde::Serialize, Debug)]
+pub struct NetworkHistoryPoint {
+    pub timestamp: String,
+    pub interface: String,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+}
+
+// 按指标分类的固定容量环形缓冲区，供 /api/history 读取，
+// 采样方式和进程/CPU/内存列表按采集周期累积的方式一致
+pub struct HistoryStore {
+    pub cpu_usage: Mutex<VecDeque<HistoryPoint>>,
+    pub memory_percent: Mutex<VecDeque<HistoryPoint>>,
+    pub load_average: Mutex<VecDeque<HistoryPoint>>,
+    pub network: Mutex<HashMap<String, VecDeque<NetworkHistoryPoint>>>,
+}
+
+impl HistoryStore {
+    pub fn new() -> Self {
+        HistoryStore {
+            cpu_usage: Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+            memory_percent: Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+            load_average: Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)),
+            network: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 把一个样本推入环形缓冲区，超过容量时丢弃最旧的样本
+fn push_capped<T>(buffer: &mut VecDeque<T>, sample: T) {
+    if buffer.len() >= HISTORY_CAPACITY {
+        buffer.pop_front();
+    }
+    buffer.push_back(sample);
+}
+
+// 后台历史采样任务：按固定周期从共享的 System/Networks 中读取当前值，
+// 写入各指标的环形缓冲区，供前端绘制趋势图而不必频繁轮询
+pub fn spawn_sampler(state: std::sync::Arc<AppState>) {
+    actix_web::rt::spawn(async move {
+        let interval = Duration::from_millis(SAMPLE_INTERVAL_MS);
+        loop {
+            actix_web::rt::time::sleep(interval).await;
+
+            let timestamp = crate::get_timestamp();
+
+            let (cpu_usage, memory_percent) = {
+                let sys = state.sys.lock().unwrap();
+                let cpu_usage = if sys.cpus().is_empty() {
+                    0.0
+                } else {
+                    sys.cpus().iter().map(|c| c.cpu_usage() as f64).sum::<f64>() / sys.cpus().len() as f64
+                };
+                let memory_percent = if sys.total_memory() > 0 {
+                    (sys.used_memory() as f64 / sys.total_memory() as f64) * 100.0
+                } else {
+                    0.0
+                };
+                (cpu_usage, memory_percent)
+            };
+            let load_average = sysinfo::System::load_average().one;
+
+            {
+                let mut buffer = state.history.cpu_usage.lock().unwrap();
+                push_capped(&mut buffer, HistoryPoint { timestamp: timestamp.clone(), value: cpu_usage });
+            }
+            {
+                let mut buffer = state.history.memory_percent.lock().unwrap();
+                push_capped(&mut buffer, HistoryPoint { timestamp: timestamp.clone(), value: memory_percent });
+            }
+            {
+                let mut buffer = state.history.load_average.lock().unwrap();
+                push_capped(&mut buffer, HistoryPoint { timestamp: timestamp.clone(), value: load_average });
+            }
+
+            // 速率由 state::spawn_refresh_task 按真实的计数器刷新周期算出，这里只读取缓存结果
+            let networks = state.networks.lock().unwrap();
+            let mut network_history = state.history.network.lock().unwrap();
+            for name in networks.iter().map(|(name, _)| name) {
+                let (rx_bytes_per_sec, tx_bytes_per_sec) = state.network_rate(name);
+                let buffer = network_history.entry(name.clone()).or_insert_with(|| VecDeque::with_capacity(HISTORY_CAPACITY));
+                push_capped(buffer, NetworkHistoryPoint {
+                    timestamp: timestamp.clone(),
+                    interface: name.clone(),
+                    rx_bytes_per_sec,
+                    tx_bytes_per_sec,
+                });
+            }
+        }
+    });
+}
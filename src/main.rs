@@ -1,10 +1,19 @@
 use actix_web::{web, App, HttpServer, HttpResponse, Responder, get, post};
 use serde::{Serialize, Deserialize};
-use sysinfo::{System, Disks, Networks};
+use sysinfo::System;
 use chrono::{DateTime, Local};
 use std::process::Command;
 use std::env;
 use std::ffi::OsStr;
+use std::sync::Arc;
+
+mod disk_usage;
+mod history;
+mod metrics;
+mod process_control;
+mod state;
+
+use state::AppState;
 
 // 定义 API 响应结构
 #[derive(Serialize, Debug)]
@@ -55,6 +64,11 @@ struct MemoryInfo {
     used_swap: u64,
     free_swap: u64,
     memory_percent: f32,
+    // 仅在请求带 ?human=true 时填充
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_memory_human: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    used_memory_human: Option<String>,
 }
 
 // 磁盘信息结构
@@ -67,6 +81,11 @@ struct DiskInfo {
     used_space: u64,
     mount_point: String,
     is_removable: bool,
+    // 仅在请求带 ?human=true 时填充
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_space_human: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    used_space_human: Option<String>,
 }
 
 // 网络信息结构
@@ -80,6 +99,9 @@ struct NetworkInfo {
     packets_transmitted: u64,
     total_received: u64,
     total_transmitted: u64,
+    // 基于与上一次采样的差值计算出的实时速率（字节/秒）
+    rx_bytes_per_sec: f64,
+    tx_bytes_per_sec: f64,
 }
 
 // 进程信息结构
@@ -94,6 +116,15 @@ struct ProcessInfo {
     command: Vec<String>,
 }
 
+// 硬件组件温度信息（CPU/GPU 等传感器）
+#[derive(Serialize, Debug)]
+struct ComponentInfo {
+    label: String,
+    temperature: f32,
+    max: f32,
+    critical: Option<f32>,
+}
+
 // 完整系统报告
 #[derive(Serialize, Debug)]
 struct FullSystemReport {
@@ -103,6 +134,7 @@ struct FullSystemReport {
     disks: Vec<DiskInfo>,
     networks: Vec<NetworkInfo>,
     processes: Vec<ProcessInfo>,
+    components: Vec<ComponentInfo>,
     timestamp: String,
 }
 
@@ -113,6 +145,56 @@ struct ProcessQuery {
     limit: Option<usize>,
 }
 
+// POST /api/disk-usage 的请求体。depth 只是递归深度的安全阀，不提供时
+// 不做任何截断（和 `du` 一样统计完整子树）；显式传一个较小的 depth 会让
+// 更深层的文件被漏计，调用方需自行承担这个取舍。
+#[derive(Deserialize)]
+struct DiskUsageRequest {
+    path: String,
+    depth: Option<usize>,
+}
+
+// POST /api/disk-usage 的返回结构
+#[derive(Serialize, Debug)]
+struct DiskUsageResponse {
+    path: String,
+    depth: usize,
+    total_bytes: u64,
+    human_readable: String,
+}
+
+// 控制是否附带人类可读的字节数字符串（?human=true）
+#[derive(Deserialize)]
+struct HumanReadableQuery {
+    human: Option<bool>,
+}
+
+// POST /api/processes/{pid}/kill 的请求体：signal 仅在 Unix 上生效
+#[derive(Deserialize)]
+struct KillRequest {
+    signal: Option<String>,
+}
+
+// /api/history 的查询参数：按指标过滤，限制返回的点数
+#[derive(Deserialize)]
+struct HistoryQuery {
+    metric: Option<String>,
+    points: Option<usize>,
+}
+
+// /api/history 的返回结构，按需只填充请求的那部分指标
+#[derive(Serialize, Debug, Default)]
+struct HistoryReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu: Option<Vec<history::HistoryPoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory: Option<Vec<history::HistoryPoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    load_average: Option<Vec<history::HistoryPoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network: Option<Vec<history::NetworkHistoryPoint>>,
+}
+
 // 辅助函数：获取当前时间戳
 fn get_timestamp() -> String {
     let now: DateTime<Local> = Local::now();
@@ -167,23 +249,38 @@ async fn index() -> impl Responder {
                 <strong>GET /api/cpu</strong> - CPU 信息
             </div>
             <div class="endpoint">
-                <strong>GET /api/memory</strong> - 内存信息
+                <strong>GET /api/memory</strong> - 内存信息 (可选参数: ?human=true)
             </div>
             <div class="endpoint">
-                <strong>GET /api/disks</strong> - 磁盘信息
+                <strong>GET /api/disks</strong> - 磁盘信息 (可选参数: ?human=true)
             </div>
             <div class="endpoint">
                 <strong>GET /api/networks</strong> - 网络信息
             </div>
+            <div class="endpoint">
+                <strong>GET /api/components</strong> - 硬件组件温度
+            </div>
             <div class="endpoint">
                 <strong>GET /api/processes</strong> - 进程列表
             </div>
             <div class="endpoint">
                 <strong>POST /api/processes/search</strong> - 搜索进程 (JSON body: {"name": "chrome", "limit": 10})
             </div>
+            <div class="endpoint">
+                <strong>POST /api/processes/{pid}/kill</strong> - 结束进程 (可选 JSON body: {"signal": "TERM"})
+            </div>
             <div class="endpoint">
                 <strong>GET /api/full-report</strong> - 完整系统报告
             </div>
+            <div class="endpoint">
+                <strong>POST /api/disk-usage</strong> - 递归统计目录占用空间 (JSON body: {"path": "/var/lib/foo", "depth": 4})
+            </div>
+            <div class="endpoint">
+                <strong>GET /api/history</strong> - 历史趋势数据 (可选参数: ?metric=cpu|memory|network&points=N)
+            </div>
+            <div class="endpoint">
+                <strong>GET /api/metrics</strong> - 各接口的延迟分布 (count/min/max/mean/p50/p90/p99/p999)
+            </div>
             <div class="endpoint">
                 <strong>GET /api/env</strong> - 环境变量
             </div>
@@ -222,10 +319,12 @@ async fn get_system_info() -> impl Responder {
 
 // 4. 获取 CPU 信息
 #[get("/api/cpu")]
-async fn get_cpu_info() -> impl Responder {
-    let mut sys = System::new_all();
-    sys.refresh_cpu();
-    
+async fn get_cpu_info(state: web::Data<AppState>) -> impl Responder {
+    // CPU 使用率由专职的后台任务（state::spawn_cpu_refresh_task）按最小间隔
+    // 持续刷新，这里直接读取最新快照即可，不必在请求里再做一次两次采样——
+    // 那样容易被并发的刷新打断，导致读数偶尔被算成 0
+    let sys = state.sys.lock().unwrap();
+
     let load_avg = System::load_average();
     let load_average = LoadAverage {
         one_min: load_avg.one,
@@ -250,10 +349,10 @@ async fn get_cpu_info() -> impl Responder {
 
 // 5. 获取内存信息
 #[get("/api/memory")]
-async fn get_memory_info() -> impl Responder {
-    let mut sys = System::new_all();
+async fn get_memory_info(state: web::Data<AppState>, query: web::Query<HumanReadableQuery>) -> impl Responder {
+    let mut sys = state.sys.lock().unwrap();
     sys.refresh_memory();
-    
+
     let total_memory = sys.total_memory();
     let used_memory = sys.used_memory();
     let free_memory = sys.free_memory();
@@ -266,7 +365,8 @@ async fn get_memory_info() -> impl Responder {
     } else {
         0.0
     };
-    
+
+    let human = query.human.unwrap_or(false);
     let info = MemoryInfo {
         total_memory,
         used_memory,
@@ -275,37 +375,47 @@ async fn get_memory_info() -> impl Responder {
         used_swap,
         free_swap,
         memory_percent,
+        total_memory_human: human.then(|| disk_usage::human_readable_bytes(total_memory)),
+        used_memory_human: human.then(|| disk_usage::human_readable_bytes(used_memory)),
     };
-    
+
     api_response(true, "内存信息获取成功", Some(info))
 }
 
 // 6. 获取磁盘信息
 #[get("/api/disks")]
-async fn get_disk_info() -> impl Responder {
-    let disks = Disks::new_with_refreshed_list();
-    
+async fn get_disk_info(state: web::Data<AppState>, query: web::Query<HumanReadableQuery>) -> impl Responder {
+    let disks = state.disks.lock().unwrap();
+    let human = query.human.unwrap_or(false);
+
     let disk_info: Vec<DiskInfo> = disks.list().iter().map(|disk| {
+        let total_space = disk.total_space();
+        let used_space = disk.total_space() - disk.available_space();
+
         DiskInfo {
             name: disk.name().to_string_lossy().to_string(),
             file_system: os_str_to_string(disk.file_system()),
-            total_space: disk.total_space(),
+            total_space,
             available_space: disk.available_space(),
-            used_space: disk.total_space() - disk.available_space(),
+            used_space,
             mount_point: disk.mount_point().to_string_lossy().to_string(),
             is_removable: disk.is_removable(),
+            total_space_human: human.then(|| disk_usage::human_readable_bytes(total_space)),
+            used_space_human: human.then(|| disk_usage::human_readable_bytes(used_space)),
         }
     }).collect();
-    
+
     api_response(true, "磁盘信息获取成功", Some(disk_info))
 }
 
 // 7. 获取网络信息
 #[get("/api/networks")]
-async fn get_network_info() -> impl Responder {
-    let networks = Networks::new_with_refreshed_list();
-    
+async fn get_network_info(state: web::Data<AppState>) -> impl Responder {
+    let networks = state.networks.lock().unwrap();
+
     let network_info: Vec<NetworkInfo> = networks.iter().map(|(name, data)| {
+        let (rx_bytes_per_sec, tx_bytes_per_sec) = state.network_rate(name);
+
         NetworkInfo {
             name: name.to_string(),
             mac_address: data.mac_address().to_string(),
@@ -315,18 +425,20 @@ async fn get_network_info() -> impl Responder {
             packets_transmitted: data.packets_transmitted(),
             total_received: data.total_received(),
             total_transmitted: data.total_transmitted(),
+            rx_bytes_per_sec,
+            tx_bytes_per_sec,
         }
     }).collect();
-    
+
     api_response(true, "网络信息获取成功", Some(network_info))
 }
 
 // 8. 获取进程列表
 #[get("/api/processes")]
-async fn get_processes() -> impl Responder {
-    let mut sys = System::new_all();
+async fn get_processes(state: web::Data<AppState>) -> impl Responder {
+    let mut sys = state.sys.lock().unwrap();
     sys.refresh_processes();
-    
+
     let processes: Vec<ProcessInfo> = sys.processes().iter()
         .map(|(pid, process)| {
             ProcessInfo {
@@ -346,10 +458,10 @@ async fn get_processes() -> impl Responder {
 
 // 9. 搜索进程 (POST 请求)
 #[post("/api/processes/search")]
-async fn search_processes(query: web::Json<ProcessQuery>) -> impl Responder {
-    let mut sys = System::new_all();
+async fn search_processes(state: web::Data<AppState>, query: web::Json<ProcessQuery>) -> impl Responder {
+    let mut sys = state.sys.lock().unwrap();
     sys.refresh_processes();
-    
+
     let mut processes: Vec<ProcessInfo> = sys.processes().iter()
         .map(|(pid, process)| {
             ProcessInfo {
@@ -376,12 +488,27 @@ async fn search_processes(query: web::Json<ProcessQuery>) -> impl Responder {
     api_response(true, &format!("找到 {} 个进程", processes.len()), Some(processes))
 }
 
+// 9b. 结束指定 PID 的进程（可选在请求体中指定信号）
+#[post("/api/processes/{pid}/kill")]
+async fn kill_process(
+    state: web::Data<AppState>,
+    path: web::Path<u32>,
+    body: Option<web::Json<KillRequest>>,
+) -> impl Responder {
+    let pid = path.into_inner();
+    let signal = body.as_ref().and_then(|b| b.signal.as_deref());
+
+    let outcome = process_control::kill_process(&state, pid, signal);
+
+    api_response(outcome.success, &outcome.reason, Some(outcome.success))
+}
+
 // 10. 获取完整系统报告
 #[get("/api/full-report")]
-async fn get_full_report() -> impl Responder {
-    let mut sys = System::new_all();
+async fn get_full_report(state: web::Data<AppState>) -> impl Responder {
+    let mut sys = state.sys.lock().unwrap();
     sys.refresh_all();
-    
+
     // 获取负载
     let load_avg = System::load_average();
     let load_average = LoadAverage {
@@ -430,10 +557,12 @@ async fn get_full_report() -> impl Responder {
         used_swap: sys.used_swap(),
         free_swap: sys.free_swap(),
         memory_percent,
+        total_memory_human: None,
+        used_memory_human: None,
     };
-    
-    // 磁盘信息（使用单独的 Disks 对象）
-    let disks = Disks::new_with_refreshed_list();
+
+    // 磁盘信息（共享的 Disks 对象）
+    let disks = state.disks.lock().unwrap();
     let disk_info: Vec<DiskInfo> = disks.list().iter().map(|disk| {
         DiskInfo {
             name: disk.name().to_string_lossy().to_string(),
@@ -443,12 +572,16 @@ async fn get_full_report() -> impl Responder {
             used_space: disk.total_space() - disk.available_space(),
             mount_point: disk.mount_point().to_string_lossy().to_string(),
             is_removable: disk.is_removable(),
+            total_space_human: None,
+            used_space_human: None,
         }
     }).collect();
     
-    // 网络信息（使用单独的 Networks 对象）
-    let networks = Networks::new_with_refreshed_list();
+    // 网络信息（共享的 Networks 对象）
+    let networks = state.networks.lock().unwrap();
     let network_info: Vec<NetworkInfo> = networks.iter().map(|(name, data)| {
+        let (rx_bytes_per_sec, tx_bytes_per_sec) = state.network_rate(name);
+
         NetworkInfo {
             name: name.to_string(),
             mac_address: data.mac_address().to_string(),
@@ -458,9 +591,22 @@ async fn get_full_report() -> impl Responder {
             packets_transmitted: data.packets_transmitted(),
             total_received: data.total_received(),
             total_transmitted: data.total_transmitted(),
+            rx_bytes_per_sec,
+            tx_bytes_per_sec,
         }
     }).collect();
-    
+
+    // 硬件组件温度信息（共享的 Components 对象）
+    let components = state.components.lock().unwrap();
+    let component_info: Vec<ComponentInfo> = components.iter().map(|component| {
+        ComponentInfo {
+            label: component.label().to_string(),
+            temperature: component.temperature(),
+            max: component.max(),
+            critical: component.critical(),
+        }
+    }).collect();
+
     // 进程信息（限制前20个）
     let processes: Vec<ProcessInfo> = sys.processes().iter()
         .take(20)
@@ -484,6 +630,7 @@ async fn get_full_report() -> impl Responder {
         disks: disk_info,
         networks: network_info,
         processes,
+        components: component_info,
         timestamp: get_timestamp(),
     };
     
@@ -526,6 +673,88 @@ async fn execute_command() -> impl Responder {
     api_response(true, "命令执行完成", Some(response))
 }
 
+// 13. 获取硬件组件温度信息
+#[get("/api/components")]
+async fn get_components_info(state: web::Data<AppState>) -> impl Responder {
+    let components = state.components.lock().unwrap();
+
+    let component_info: Vec<ComponentInfo> = components.iter().map(|component| {
+        ComponentInfo {
+            label: component.label().to_string(),
+            temperature: component.temperature(),
+            max: component.max(),
+            critical: component.critical(),
+        }
+    }).collect();
+
+    api_response(true, "硬件组件信息获取成功", Some(component_info))
+}
+
+// 14. 递归统计目录占用空间
+#[post("/api/disk-usage")]
+async fn get_disk_usage(req: web::Json<DiskUsageRequest>) -> impl Responder {
+    // 不传 depth 时不限制递归深度，保证 total_bytes 是完整子树的总和
+    let depth = req.depth.unwrap_or(usize::MAX);
+    let path = std::path::Path::new(&req.path);
+
+    match disk_usage::walk_dir_size(path, depth) {
+        Ok(total_bytes) => {
+            let response = DiskUsageResponse {
+                path: req.path.clone(),
+                depth,
+                total_bytes,
+                human_readable: disk_usage::human_readable_bytes(total_bytes),
+            };
+            api_response(true, "目录占用空间统计成功", Some(response))
+        }
+        Err(err) => api_response::<DiskUsageResponse>(false, &format!("统计失败: {}", err), None),
+    }
+}
+
+// 15. 获取历史趋势数据（按固定周期采集，存放在环形缓冲区中）
+#[get("/api/history")]
+async fn get_history(state: web::Data<AppState>, query: web::Query<HistoryQuery>) -> impl Responder {
+    let points = query.points.unwrap_or(usize::MAX);
+    let take_last = |buffer: &std::collections::VecDeque<history::HistoryPoint>| -> Vec<history::HistoryPoint> {
+        let skip = buffer.len().saturating_sub(points);
+        buffer.iter().skip(skip).cloned().collect()
+    };
+
+    let mut report = HistoryReport::default();
+
+    match query.metric.as_deref() {
+        Some("cpu") => {
+            report.cpu = Some(take_last(&state.history.cpu_usage.lock().unwrap()));
+        }
+        Some("memory") => {
+            report.memory = Some(take_last(&state.history.memory_percent.lock().unwrap()));
+        }
+        Some("network") => {
+            let network_history = state.history.network.lock().unwrap();
+            let mut points_out: Vec<history::NetworkHistoryPoint> = Vec::new();
+            for buffer in network_history.values() {
+                let skip = buffer.len().saturating_sub(points);
+                points_out.extend(buffer.iter().skip(skip).cloned());
+            }
+            report.network = Some(points_out);
+        }
+        _ => {
+            report.cpu = Some(take_last(&state.history.cpu_usage.lock().unwrap()));
+            report.memory = Some(take_last(&state.history.memory_percent.lock().unwrap()));
+            report.load_average = Some(take_last(&state.history.load_average.lock().unwrap()));
+        }
+    }
+
+    api_response(true, "历史趋势数据获取成功", Some(report))
+}
+
+// 16. 获取各接口的延迟分布（由 RequestTiming 中间件记录）
+#[get("/api/metrics")]
+async fn get_metrics(store: web::Data<metrics::MetricsStore>) -> impl Responder {
+    let snapshot = store.snapshot();
+    api_response(true, "延迟指标获取成功", Some(snapshot))
+}
+
 // 主函数
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -533,9 +762,21 @@ async fn main() -> std::io::Result<()> {
     println!("📡 服务器运行在: http://localhost:8080");
     println!("📖 访问 http://localhost:8080 查看 API 文档");
     println!("🛑 按 Ctrl+C 停止服务器\n");
-    
-    HttpServer::new(|| {
+
+    // 共享的系统状态，只初始化一次，由后台任务持续刷新
+    let app_state = Arc::new(AppState::new());
+    state::spawn_refresh_task(app_state.clone());
+    state::spawn_cpu_refresh_task(app_state.clone());
+    history::spawn_sampler(app_state.clone());
+
+    // 每个路由的延迟直方图，由 RequestTiming 中间件写入
+    let metrics_store = Arc::new(metrics::MetricsStore::new());
+
+    HttpServer::new(move || {
         App::new()
+            .app_data(web::Data::from(app_state.clone()))
+            .app_data(web::Data::from(metrics_store.clone()))
+            .wrap(metrics::RequestTiming { store: metrics_store.clone() })
             // 注册所有路由
             .service(index)
             .service(health_check)
@@ -544,9 +785,14 @@ async fn main() -> std::io::Result<()> {
             .service(get_memory_info)
             .service(get_disk_info)
             .service(get_network_info)
+            .service(get_components_info)
             .service(get_processes)
             .service(search_processes)
+            .service(kill_process)
             .service(get_full_report)
+            .service(get_disk_usage)
+            .service(get_history)
+            .service(get_metrics)
             .service(get_env_vars)
             .service(execute_command)
     })
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::LocalBoxFuture;
+use serde::Serialize;
+
+// 直方图能记录的最大耗时（微秒）与有效数字位数。
+// 更多有效数字 -> 更细的子桶，但占用内存也更多；2 位数字对延迟监控已经足够。
+const MAX_TRACKABLE_MICROS: u64 = 60_000_000; // 60s
+const SIGNIFICANT_DIGITS: u32 = 2;
+
+// 一个 HDR（High Dynamic Range）风格的直方图：按数量级（最高有效位）分桶，
+// 每个桶内再线性细分为若干子桶，记录是 O(1)，查询分位数时按桶累加计数直到达到目标排名。
+// 内存占用固定（桶数 * 子桶数），相对误差在微秒到分钟的范围内保持恒定。
+pub struct HdrHistogram {
+    max_value: u64,
+    sub_buckets: usize,
+    bucket_count: usize,
+    counts: Vec<u64>,
+    total_count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl HdrHistogram {
+    pub fn new(max_value: u64, significant_digits: u32) -> Self {
+        // 子桶数取 2^(s*log2(10))，即每个数量级内大约能分辨 10^s 个不同的值
+        let sub_bucket_bits = ((significant_digits as f64) * 10f64.log2()).ceil() as u32;
+        let sub_buckets = 1usize << sub_bucket_bits;
+        let bucket_count = (64 - max_value.max(1).leading_zeros()) as usize + 1;
+
+        HdrHistogram {
+            max_value,
+            sub_buckets,
+            bucket_count,
+            counts: vec![0u64; bucket_count * sub_buckets],
+            total_count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    // 某个数量级桶覆盖的取值范围 [start, end)
+    fn bucket_range(&self, bucket: usize) -> (u64, u64) {
+        let start = 1u64 << bucket;
+        let end = if bucket + 1 < 64 { 1u64 << (bucket + 1) } else { u64::MAX };
+        (start, end)
+    }
+
+    fn index_for(&self, value: u64) -> (usize, usize) {
+        let value = value.clamp(1, self.max_value);
+        let magnitude = 64 - value.leading_zeros();
+        let bucket = (magnitude as usize - 1).min(self.bucket_count - 1);
+        let (start, end) = self.bucket_range(bucket);
+        let width = (end - start).max(1);
+        let offset = value - start;
+        let sub = ((offset as u128 * self.sub_buckets as u128) / width as u128) as usize;
+        (bucket, sub.min(self.sub_buckets - 1))
+    }
+
+    pub fn record(&mut self, value: u64) {
+        let clamped = value.min(self.max_value);
+        let (bucket, sub) = self.index_for(clamped);
+        self.counts[bucket * self.sub_buckets + sub] += 1;
+        self.total_count += 1;
+        self.sum += clamped as u128;
+        self.min = self.min.min(clamped);
+        self.max = self.max.max(clamped);
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.total_count == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.total_count as f64
+        }
+    }
+
+    // 从最小的桶开始累加计数，直到达到目标排名，返回该子桶覆盖区间的起点作为近似值
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.total_count == 0 {
+            return 0;
+        }
+        let target = ((p / 100.0) * self.total_count as f64).ceil().max(1.0) as u64;
+        let mut cumulative = 0u64;
+
+        for bucket in 0..self.bucket_count {
+            let (start, end) = self.bucket_range(bucket);
+            let width = (end - start).max(1);
+
+            for sub in 0..self.sub_buckets {
+                cumulative += self.counts[bucket * self.sub_buckets + sub];
+                if cumulative >= target {
+                    // 与 index_for 的正向映射保持对称：offset = sub * width / sub_buckets。
+                    // 直接用 sub_width = width / sub_buckets 在 width < sub_buckets 时会被
+                    // 取整钳制到 1，导致重建出的值越过桶的真实上界（end）。
+                    let offset = (sub as u128 * width as u128) / self.sub_buckets as u128;
+                    return start + offset as u64;
+                }
+            }
+        }
+
+        self.max
+    }
+}
+
+// 单个路由的延迟统计快照，供 /api/metrics 序列化返回
+#[derive(Serialize, Debug)]
+pub struct RouteStats {
+    pub count: u64,
+    pub min_micros: u64,
+    pub max_micros: u64,
+    pub mean_micros: f64,
+    pub p50_micros: u64,
+    pub p90_micros: u64,
+    pub p99_micros: u64,
+    pub p999_micros: u64,
+}
+
+impl From<&HdrHistogram> for RouteStats {
+    fn from(h: &HdrHistogram) -> Self {
+        RouteStats {
+            count: h.total_count,
+            min_micros: if h.total_count == 0 { 0 } else { h.min },
+            max_micros: h.max,
+            mean_micros: h.mean(),
+            p50_micros: h.percentile(50.0),
+            p90_micros: h.percentile(90.0),
+            p99_micros: h.percentile(99.0),
+            p999_micros: h.percentile(99.9),
+        }
+    }
+}
+
+// 每条路由一个直方图，记录该路由的响应延迟分布
+pub struct MetricsStore {
+    pub histograms: Mutex<HashMap<String, HdrHistogram>>,
+}
+
+impl MetricsStore {
+    pub fn new() -> Self {
+        MetricsStore {
+            histograms: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, route: &str, elapsed_micros: u64) {
+        let mut histograms = self.histograms.lock().unwrap();
+        histograms
+            .entry(route.to_string())
+            .or_insert_with(|| HdrHistogram::new(MAX_TRACKABLE_MICROS, SIGNIFICANT_DIGITS))
+            .record(elapsed_micros);
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, RouteStats> {
+        let histograms = self.histograms.lock().unwrap();
+        histograms.iter().map(|(route, h)| (route.clone(), RouteStats::from(h))).collect()
+    }
+}
+
+impl Default for MetricsStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Actix 中间件：记录每个请求的响应耗时到对应路由的直方图里
+pub struct RequestTiming {
+    pub store: Arc<MetricsStore>,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTiming
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTimingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTimingMiddleware {
+            service,
+            store: self.store.clone(),
+        }))
+    }
+}
+
+pub struct RequestTimingMiddleware<S> {
+    service: S,
+    store: Arc<MetricsStore>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let route = req.match_pattern().unwrap_or_else(|| req.path().to_string());
+        let store = self.store.clone();
+        let start = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            store.record(&route, start.elapsed().as_micros() as u64);
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use sysinfo::{Pid, Signal};
+
+use crate::state::AppState;
+
+// 结束进程请求的结果：是否真的发出了信号，以及一句说明（被拒绝时给出原因）
+pub struct KillOutcome {
+    pub success: bool,
+    pub reason: String,
+}
+
+// 不允许被结束的进程名（大小写不敏感），通过环境变量 PROCESS_KILL_DENYLIST
+// 以逗号分隔配置，例如 "systemd,launchd"
+fn denylisted_names() -> HashSet<String> {
+    std::env::var("PROCESS_KILL_DENYLIST")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+// 把请求体里的信号名解析成 sysinfo::Signal，未识别的名称视为非法
+fn parse_signal(name: &str) -> Option<Signal> {
+    match name.to_uppercase().as_str() {
+        "HUP" | "SIGHUP" => Some(Signal::Hangup),
+        "INT" | "SIGINT" => Some(Signal::Interrupt),
+        "QUIT" | "SIGQUIT" => Some(Signal::Quit),
+        "KILL" | "SIGKILL" => Some(Signal::Kill),
+        "TERM" | "SIGTERM" => Some(Signal::Term),
+        "USR1" | "SIGUSR1" => Some(Signal::User1),
+        "USR2" | "SIGUSR2" => Some(Signal::User2),
+        "STOP" | "SIGSTOP" => Some(Signal::Stop),
+        "CONT" | "SIGCONT" => Some(Signal::Continue),
+        _ => None,
+    }
+}
+
+// 结束指定 PID 的进程（可选带信号名）。内置防护：拒绝结束 PID 1 和服务自身，
+// 并支持通过 PROCESS_KILL_DENYLIST 按进程名拉黑。
+pub fn kill_process(state: &AppState, pid: u32, signal_name: Option<&str>) -> KillOutcome {
+    if pid == 1 {
+        return KillOutcome {
+            success: false,
+            reason: "拒绝结束 PID 1".to_string(),
+        };
+    }
+
+    if pid == std::process::id() {
+        return KillOutcome {
+            success: false,
+            reason: "拒绝结束服务自身进程".to_string(),
+        };
+    }
+
+    let signal = match signal_name {
+        Some(name) => match parse_signal(name) {
+            Some(signal) => Some(signal),
+            None => {
+                return KillOutcome {
+                    success: false,
+                    reason: format!("不支持的信号: {}", name),
+                }
+            }
+        },
+        None => None,
+    };
+
+    let mut sys = state.sys.lock().unwrap();
+    let target = Pid::from_u32(pid);
+
+    let process = match sys.process(target) {
+        Some(process) => process,
+        None => {
+            return KillOutcome {
+                success: false,
+                reason: format!("未找到 PID {}", pid),
+            }
+        }
+    };
+
+    let denylist = denylisted_names();
+    if denylist.contains(&process.name().to_lowercase()) {
+        return KillOutcome {
+            success: false,
+            reason: format!("进程 {} 在拉黑名单中，拒绝结束", process.name()),
+        };
+    }
+
+    let delivered = match signal {
+        Some(signal) => process.kill_with(signal).unwrap_or(false),
+        None => process.kill(),
+    };
+
+    sys.refresh_processes();
+
+    if delivered {
+        KillOutcome {
+            success: true,
+            reason: format!("已向 PID {} 发送信号", pid),
+        }
+    } else {
+        KillOutcome {
+            success: false,
+            reason: format!("向 PID {} 发送信号失败", pid),
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use sysinfo::{Components, Disks, Networks, RefreshKind, System};
+
+use crate::history::HistoryStore;
+
+// 默认的后台刷新间隔（毫秒）
+const DEFAULT_REFRESH_INTERVAL_MS: u64 = 1000;
+
+// sysinfo 要求两次 CPU 刷新之间至少间隔这么久，否则 usage 读数没有意义。
+// 专职的 CPU 刷新任务（spawn_cpu_refresh_task）按这个节奏独立运行，
+// 不会被 spawn_refresh_task 里其它指标的全量刷新打断两次采样之间的窗口。
+pub const MIN_CPU_REFRESH_INTERVAL: Duration = Duration::from_millis(220);
+
+// 某一网络接口在某次采样时的累计收发字节数，用于和下一次采样做差值
+#[derive(Clone, Copy)]
+struct NetworkSample {
+    received_bytes: u64,
+    transmitted_bytes: u64,
+    at: Instant,
+}
+
+// 共享的系统状态，初始化一次并在后台持续刷新，
+// 避免每次请求都重新创建 System / Disks / Networks
+pub struct AppState {
+    pub sys: Mutex<System>,
+    pub disks: Mutex<Disks>,
+    pub networks: Mutex<Networks>,
+    pub components: Mutex<Components>,
+    // 每个接口上一次后台刷新时的累计字节数基准，只由后台刷新任务写入，
+    // 这样 elapsed 对应的是真实的计数器刷新周期，而不是"距上次有请求读取过去了多久"
+    network_prev: Mutex<HashMap<String, NetworkSample>>,
+    // 每个接口最近一次算出的 (接收速率, 发送速率)（单位：字节/秒），供请求读取
+    network_rates: Mutex<HashMap<String, (f64, f64)>>,
+    // 各指标的历史采样环形缓冲区
+    pub history: HistoryStore,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        let sys = System::new_all();
+        let disks = Disks::new_with_refreshed_list();
+        let networks = Networks::new_with_refreshed_list();
+        let components = Components::new_with_refreshed_list();
+
+        AppState {
+            sys: Mutex::new(sys),
+            disks: Mutex::new(disks),
+            networks: Mutex::new(networks),
+            components: Mutex::new(components),
+            network_prev: Mutex::new(HashMap::new()),
+            network_rates: Mutex::new(HashMap::new()),
+            history: HistoryStore::new(),
+        }
+    }
+
+    // 读取某个接口最近一次算出的 (接收速率, 发送速率)（单位：字节/秒）。
+    // 速率只在后台刷新任务里按真实的计数器刷新周期计算一次，这里只是读取缓存结果：
+    // 请求本身不会修改基准，短时间内多次请求也不会互相抢基准导致速率被算成 0。
+    pub fn network_rate(&self, interface: &str) -> (f64, f64) {
+        self.network_rates
+            .lock()
+            .unwrap()
+            .get(interface)
+            .copied()
+            .unwrap_or((0.0, 0.0))
+    }
+
+    // 用本次后台刷新读到的累计字节数和上一次后台刷新的基准算出速率，写入缓存，
+    // 再把本次的累计值存为下一次的基准。只应由后台刷新任务调用。
+    fn refresh_network_rates(&self, networks: &Networks) {
+        let now = Instant::now();
+        let mut prev = self.network_prev.lock().unwrap();
+        let mut rates = self.network_rates.lock().unwrap();
+
+        for (name, data) in networks.iter() {
+            let received_bytes = data.total_received();
+            let transmitted_bytes = data.total_transmitted();
+
+            let rate = match prev.get(name) {
+                Some(sample) => {
+                    let elapsed = now.duration_since(sample.at).as_secs_f64();
+                    if elapsed > 0.0 {
+                        let rx_rate = received_bytes.saturating_sub(sample.received_bytes) as f64 / elapsed;
+                        let tx_rate = transmitted_bytes.saturating_sub(sample.transmitted_bytes) as f64 / elapsed;
+                        (rx_rate, tx_rate)
+                    } else {
+                        (0.0, 0.0)
+                    }
+                }
+                None => (0.0, 0.0),
+            };
+
+            rates.insert(name.clone(), rate);
+            prev.insert(
+                name.clone(),
+                NetworkSample { received_bytes, transmitted_bytes, at: now },
+            );
+        }
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 在后台按固定间隔刷新共享的 System/Disks/Networks/Components，
+// 让各接口读取到的数据始终是近期的快照，而不必每次请求都全量重建。
+// CPU 使用率不在这里刷新——它由 spawn_cpu_refresh_task 独占，避免两者的
+// 刷新互相打断对方的两次采样窗口。
+pub fn spawn_refresh_task(state: std::sync::Arc<AppState>) {
+    actix_web::rt::spawn(async move {
+        let interval = Duration::from_millis(DEFAULT_REFRESH_INTERVAL_MS);
+        loop {
+            actix_web::rt::time::sleep(interval).await;
+
+            if let Ok(mut sys) = state.sys.lock() {
+                sys.refresh_specifics(RefreshKind::everything().without_cpu());
+            }
+            if let Ok(mut disks) = state.disks.lock() {
+                disks.refresh();
+            }
+            if let Ok(mut networks) = state.networks.lock() {
+                networks.refresh();
+                state.refresh_network_rates(&networks);
+            }
+            if let Ok(mut components) = state.components.lock() {
+                components.refresh();
+            }
+        }
+    });
+}
+
+// 专职刷新 CPU 使用率的后台任务，按 sysinfo 要求的最小间隔独立运行，
+// 不会被 spawn_refresh_task 里其它指标的全量刷新打断采样窗口
+pub fn spawn_cpu_refresh_task(state: std::sync::Arc<AppState>) {
+    actix_web::rt::spawn(async move {
+        loop {
+            actix_web::rt::time::sleep(MIN_CPU_REFRESH_INTERVAL).await;
+
+            if let Ok(mut sys) = state.sys.lock() {
+                sys.refresh_cpu_usage();
+            }
+        }
+    });
+}